@@ -22,27 +22,63 @@ extern crate actix_rt;
 
 use actix_web::dev::Body;
 use actix_web::dev::Server;
-use actix_web::http::StatusCode;
+use actix_web::dev::Service;
+use actix_web::http::header::HeaderMap;
+use actix_web::http::{HeaderName, HeaderValue, StatusCode};
 use actix_web::web::Bytes;
 use actix_web::{middleware, web, App, HttpRequest, HttpResponse, HttpServer};
-use codec::capabilities::{CapabilityProvider, Dispatcher, NullDispatcher};
+use codec::capabilities::{
+    CapabilityDescriptor, CapabilityProvider, Dispatcher, NullDispatcher, OperationDirection,
+};
 use codec::core::CapabilityConfiguration;
+use futures::future::{ok, Either};
+use rustls::internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use rustls::{NoClientAuth, ServerConfig};
 use std::collections::HashMap;
 use std::error::Error as StdError;
+use std::fs::File;
+use std::io::BufReader;
 use std::sync::Arc;
 use std::sync::RwLock;
-use wascc_codec::core::{OP_BIND_ACTOR, OP_REMOVE_ACTOR};
+use wascc_codec::core::{OP_BIND_ACTOR, OP_GET_CAPABILITY_DESCRIPTOR, OP_REMOVE_ACTOR};
+use wascc_codec::http::OP_HANDLE_REQUEST;
 use wascc_codec::{deserialize, serialize};
 
 const CAPABILITY_ID: &str = "wascc:http_server";
+const REVISION: u32 = 0;
 
 #[cfg(not(feature = "static_plugin"))]
 capability_provider!(HttpServerProvider, HttpServerProvider::new);
 
+/// A single actor's registration on a shared listener: which module should
+/// receive the request, and the (optional) host/path-prefix rule that steers
+/// traffic to it. An entry with neither `host` nor `path_prefix` is a
+/// catch-all, used when exactly one actor occupies a listener.
+struct RouteEntry {
+    module_id: String,
+    host: Option<String>,
+    path_prefix: Option<String>,
+}
+
+/// A running Actix-web server bound to a single address, and the routing
+/// table used to dispatch requests to the actor(s) sharing it. `server` is
+/// `None` until the spawned bind thread finishes binding.
+struct Listener {
+    server: RwLock<Option<Server>>,
+    routes: Arc<RwLock<Vec<RouteEntry>>>,
+    tls_enabled: bool,
+    compress_enabled: bool,
+    max_body_bytes: usize,
+    /// Whether teardown drains outstanding requests rather than closing
+    /// connections immediately. Defaults to `true`.
+    graceful_shutdown: bool,
+}
+
 /// An Actix-web implementation of the `wascc:http_server` capability specification
 pub struct HttpServerProvider {
     dispatcher: Arc<RwLock<Box<dyn Dispatcher>>>,
-    servers: Arc<RwLock<HashMap<String, Server>>>,
+    servers: Arc<RwLock<HashMap<String, Listener>>>,
+    module_addrs: Arc<RwLock<HashMap<String, String>>>,
 }
 
 impl HttpServerProvider {
@@ -53,27 +89,44 @@ impl HttpServerProvider {
         Self::default()
     }
 
-    /// Stops a running web server, freeing up its associated port
+    /// Removes an actor's route from its listener, tearing down the listener
+    /// itself once the last actor sharing it has been removed.
     fn terminate_server(&self, module: &str) {
-        {
-            let lock = self.servers.read().unwrap();
-            if !lock.contains_key(module) {
+        let bind_addr = match self.module_addrs.write().unwrap().remove(module) {
+            Some(addr) => addr,
+            None => {
                 error!(
                     "Received request to stop server for non-configured actor {}. Igoring.",
                     module
                 );
                 return;
             }
-            let server = lock.get(module).unwrap();
-            let _ = server.stop(true);
-        }
-        {
-            let mut lock = self.servers.write().unwrap();
-            lock.remove(module).unwrap();
+        };
+
+        let mut lock = self.servers.write().unwrap();
+        let is_empty = {
+            let listener = lock.get(&bind_addr).unwrap();
+            let mut routes = listener.routes.write().unwrap();
+            routes.retain(|r| r.module_id != module);
+            routes.is_empty()
+        };
+        if is_empty {
+            let listener = lock.remove(&bind_addr).unwrap();
+            // `stop(true)` (the default) drains outstanding requests, up to the
+            // listener's `shutdown_timeout` (set from `GRACEFUL_SHUTDOWN_SECS`
+            // at bind time); `stop(false)` drops connections immediately, and
+            // only happens when `GRACEFUL_SHUTDOWN_SECS=0` opted out of draining.
+            // The bind thread may not have finished binding yet, in which case
+            // there's nothing to stop.
+            if let Some(server) = listener.server.write().unwrap().take() {
+                let _ = server.stop(listener.graceful_shutdown);
+            }
         }
     }
 
-    /// Starts a new web server and binds to the appropriate port
+    /// Starts a new web server and binds to the appropriate port, or, if an
+    /// actor is already listening on that address, registers this actor's
+    /// route on the existing listener instead of binding a second time.
     fn spawn_server(&self, cfgvals: &CapabilityConfiguration) {
         let bind_port = match cfgvals.values.get("PORT") {
             Some(s) => s.clone(),
@@ -84,35 +137,293 @@ impl HttpServerProvider {
             None => "0.0.0.0".to_string(),
         };
         let bind_addr = format!("{}:{}", bind_host, bind_port);
+        let module_id = cfgvals.module.clone();
+        // NOTE: `HOST` above is the interface this listener binds to, so virtual-host
+        // routing on a shared listener is keyed off `VHOST` instead to avoid colliding with it.
+        let route = RouteEntry {
+            module_id: module_id.clone(),
+            host: cfgvals.values.get("VHOST").map(|s| s.to_ascii_lowercase()),
+            path_prefix: cfgvals.values.get("PATH_PREFIX").cloned(),
+        };
+
+        let tls_config = match tls_config_from(cfgvals) {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                error!(
+                    "Refusing to bind {} for actor {}: {}",
+                    bind_addr, module_id, e
+                );
+                return;
+            }
+        };
+        let tls_enabled = tls_config.is_some();
+        let compress_enabled = compress_enabled_from(cfgvals);
+        let max_body_bytes: usize = cfgvals
+            .values
+            .get("MAX_BODY_BYTES")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(256 * 1024 * 1024);
+        let graceful_secs: Option<u64> = cfgvals
+            .values
+            .get("GRACEFUL_SHUTDOWN_SECS")
+            .and_then(|v| v.parse().ok());
+        // Graceful draining is the default; `GRACEFUL_SHUTDOWN_SECS=0` is the
+        // explicit opt-out into an immediate, connection-dropping shutdown.
+        let graceful_shutdown = graceful_secs != Some(0);
+
+        // Reserve (or join) the bind address under a single write-lock
+        // acquisition, so two actors racing to bind the same address can't
+        // both see "no listener yet" and both attempt to bind.
+        let routes = {
+            let mut lock = self.servers.write().unwrap();
+            if let Some(listener) = lock.get(&bind_addr) {
+                warn_on_config_mismatch(
+                    &bind_addr,
+                    &module_id,
+                    listener,
+                    tls_enabled,
+                    compress_enabled,
+                    max_body_bytes,
+                    graceful_shutdown,
+                );
+                info!(
+                    "Registering actor {} on shared listener {}",
+                    module_id, bind_addr
+                );
+                listener.routes.write().unwrap().push(route);
+                drop(lock);
+                self.module_addrs
+                    .write()
+                    .unwrap()
+                    .insert(module_id, bind_addr);
+                return;
+            }
+
+            let routes = Arc::new(RwLock::new(vec![route]));
+            lock.insert(
+                bind_addr.clone(),
+                Listener {
+                    server: RwLock::new(None),
+                    routes: routes.clone(),
+                    tls_enabled,
+                    compress_enabled,
+                    max_body_bytes,
+                    graceful_shutdown,
+                },
+            );
+            routes
+        };
 
         let disp = self.dispatcher.clone();
-        let module_id = cfgvals.module.clone();
 
         info!("Received HTTP Server configuration for {}", module_id);
         let servers = self.servers.clone();
+        self.module_addrs
+            .write()
+            .unwrap()
+            .insert(module_id.clone(), bind_addr.clone());
 
         std::thread::spawn(move || {
             let module = module_id.clone();
             let sys = actix_rt::System::new(&module);
-            let server = HttpServer::new(move || {
+            let app_routes = routes.clone();
+            // The `Bytes` extractor already maps an over-limit body to
+            // `PayloadError::Overflow`, which has its own `ResponseError`
+            // impl returning 413, so no custom error handler is needed here.
+            let payload_cfg = web::PayloadConfig::new(max_body_bytes);
+            let factory = move || {
                 App::new()
                     .wrap(middleware::Logger::default())
+                    .wrap(middleware::Condition::new(
+                        compress_enabled,
+                        middleware::Compress::default(),
+                    ))
+                    .wrap_fn(move |req, srv| {
+                        if compress_enabled && !accepts_supported_encoding(req.headers()) {
+                            Either::Left(ok(req.into_response(
+                                HttpResponse::NotAcceptable().finish().into_body(),
+                            )))
+                        } else {
+                            Either::Right(srv.call(req))
+                        }
+                    })
                     .data(disp.clone())
-                    .data(module.clone())
+                    .data(app_routes.clone())
+                    .app_data(payload_cfg.clone())
                     .default_service(web::route().to(request_handler))
-            })
-            .bind(bind_addr)
+            };
+            let addr = bind_addr.clone();
+            let mut builder = match tls_config {
+                Some(config) => HttpServer::new(factory).bind_rustls(addr, config),
+                None => HttpServer::new(factory).bind(addr),
+            }
             .unwrap()
             .disable_signals()
-            .run();
+            .system_exit();
+            if let Some(secs) = graceful_secs.filter(|secs| *secs > 0) {
+                builder = builder.shutdown_timeout(secs);
+            }
+            let server = builder.run();
 
-            servers.write().unwrap().insert(module_id.clone(), server);
+            // The listener we reserved may have been torn down (or replaced
+            // by a new reservation at the same address) while this bind was
+            // in flight, e.g. if the only actor on it was removed. Identify
+            // "our" reservation by its `routes` Arc rather than just the
+            // bind address being present, and stop the now-orphaned server
+            // immediately instead of leaking it and blocking this thread
+            // forever in `sys.run()`.
+            let is_still_ours = servers
+                .read()
+                .unwrap()
+                .get(&bind_addr)
+                .map_or(false, |listener| Arc::ptr_eq(&listener.routes, &routes));
+            if is_still_ours {
+                if let Some(listener) = servers.read().unwrap().get(&bind_addr) {
+                    *listener.server.write().unwrap() = Some(server);
+                }
+            } else {
+                warn!(
+                    "Listener {} was torn down before actor {} finished binding; stopping orphaned server",
+                    bind_addr, module
+                );
+                let _ = server.stop(false);
+            }
 
             let _ = sys.run();
         });
     }
 }
 
+/// Logs a warning when an actor joining an already-bound shared listener
+/// specifies per-listener configuration (TLS/compression/body-limit/shutdown
+/// draining) that differs from what the first actor on that listener set up.
+/// These settings apply to the whole listener, so the joining actor's values
+/// are otherwise silently discarded in favor of the existing ones.
+fn warn_on_config_mismatch(
+    bind_addr: &str,
+    module_id: &str,
+    listener: &Listener,
+    tls_enabled: bool,
+    compress_enabled: bool,
+    max_body_bytes: usize,
+    graceful_shutdown: bool,
+) {
+    if tls_enabled != listener.tls_enabled {
+        warn!(
+            "Actor {} requested TLS={} when joining shared listener {}, but it is already configured with TLS={}; keeping the existing listener's setting",
+            module_id, tls_enabled, bind_addr, listener.tls_enabled
+        );
+    }
+    if compress_enabled != listener.compress_enabled {
+        warn!(
+            "Actor {} requested COMPRESS={} when joining shared listener {}, but it is already configured with COMPRESS={}; keeping the existing listener's setting",
+            module_id, compress_enabled, bind_addr, listener.compress_enabled
+        );
+    }
+    if max_body_bytes != listener.max_body_bytes {
+        warn!(
+            "Actor {} requested MAX_BODY_BYTES={} when joining shared listener {}, but it is already configured with MAX_BODY_BYTES={}; keeping the existing listener's setting",
+            module_id, max_body_bytes, bind_addr, listener.max_body_bytes
+        );
+    }
+    if graceful_shutdown != listener.graceful_shutdown {
+        warn!(
+            "Actor {} requested graceful_shutdown={} when joining shared listener {}, but it is already configured with graceful_shutdown={}; keeping the existing listener's setting",
+            module_id, graceful_shutdown, bind_addr, listener.graceful_shutdown
+        );
+    }
+}
+
+/// Parses the `COMPRESS` capability config value into a simple on/off switch.
+/// When enabled, which algorithm (if any) actix-web's `Compress` middleware
+/// picks is driven by the client's `Accept-Encoding` header; a request whose
+/// `Accept-Encoding` names no encoding this server can produce is rejected
+/// with 406 by `accepts_supported_encoding` rather than served uncompressed.
+fn compress_enabled_from(cfgvals: &CapabilityConfiguration) -> bool {
+    match cfgvals.values.get("COMPRESS") {
+        Some(v) => !matches!(
+            v.trim().to_ascii_lowercase().as_str(),
+            "" | "0" | "false" | "off" | "no"
+        ),
+        None => false,
+    }
+}
+
+/// Whether `headers` names at least one encoding actix-web's `Compress`
+/// middleware can produce (`identity`, `gzip`, `br`, `deflate`, or `*`), per
+/// RFC 7231 §5.3.4. No `Accept-Encoding` header at all means any encoding,
+/// including `identity`, is acceptable.
+fn accepts_supported_encoding(headers: &HeaderMap) -> bool {
+    let value = match headers
+        .get(actix_web::http::header::ACCEPT_ENCODING)
+        .and_then(|h| h.to_str().ok())
+    {
+        Some(v) => v,
+        None => return true,
+    };
+
+    value.split(',').any(|candidate| {
+        let token = candidate
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        matches!(token.as_str(), "identity" | "gzip" | "br" | "deflate" | "*")
+    })
+}
+
+/// Builds a `rustls` server configuration from the `TLS_CERT`/`TLS_CERT_PATH`
+/// and `TLS_KEY`/`TLS_KEY_PATH` capability configuration values. Returns
+/// `Ok(None)` when TLS wasn't configured, and `Err` when it was configured
+/// but the cert/key couldn't be loaded or parsed.
+fn tls_config_from(cfgvals: &CapabilityConfiguration) -> Result<Option<ServerConfig>, String> {
+    if cfgvals.values.get("TLS_CERT_PATH").is_none() && cfgvals.values.get("TLS_CERT").is_none() {
+        return Ok(None);
+    }
+
+    let cert_chain = match cfgvals.values.get("TLS_CERT_PATH") {
+        Some(path) => {
+            let f = File::open(path)
+                .map_err(|e| format!("failed to open TLS cert file {}: {}", path, e))?;
+            certs(&mut BufReader::new(f))
+                .map_err(|_| format!("failed to parse TLS cert chain at {}", path))?
+        }
+        None => {
+            let pem = cfgvals.values.get("TLS_CERT").unwrap();
+            certs(&mut BufReader::new(pem.as_bytes()))
+                .map_err(|_| "failed to parse inline TLS_CERT PEM".to_string())?
+        }
+    };
+
+    let key_reader = match cfgvals.values.get("TLS_KEY_PATH") {
+        Some(path) => std::fs::read(path)
+            .map_err(|e| format!("failed to open TLS key file {}: {}", path, e))?,
+        None => cfgvals
+            .values
+            .get("TLS_KEY")
+            .ok_or_else(|| "TLS_CERT(_PATH) was set without a corresponding TLS_KEY(_PATH)".to_string())?
+            .as_bytes()
+            .to_vec(),
+    };
+
+    let mut keys =
+        pkcs8_private_keys(&mut BufReader::new(key_reader.as_slice())).unwrap_or_default();
+    if keys.is_empty() {
+        keys = rsa_private_keys(&mut BufReader::new(key_reader.as_slice())).unwrap_or_default();
+    }
+    if keys.is_empty() {
+        return Err("failed to parse TLS private key as either PKCS#8 or RSA".to_string());
+    }
+
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config
+        .set_single_cert(cert_chain, keys.remove(0))
+        .map_err(|e| format!("failed to apply TLS certificate/key to server config: {}", e))?;
+
+    Ok(Some(config))
+}
+
 impl Default for HttpServerProvider {
     fn default() -> Self {
         match env_logger::try_init() {
@@ -122,6 +433,7 @@ impl Default for HttpServerProvider {
         HttpServerProvider {
             dispatcher: Arc::new(RwLock::new(Box::new(NullDispatcher::new()))),
             servers: Arc::new(RwLock::new(HashMap::new())),
+            module_addrs: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -166,18 +478,47 @@ impl CapabilityProvider for HttpServerProvider {
             info!("Removing actor configuration for {}", cfgvals.module);
             self.terminate_server(&cfgvals.module);
             Ok(vec![])
+        } else if op == OP_GET_CAPABILITY_DESCRIPTOR {
+            Ok(serialize(capability_descriptor())?)
         } else {
             Err(format!("Unknown operation: {}", op).into())
         }
     }
 }
 
+/// Builds the descriptor that advertises this provider's capability ID, name,
+/// version and supported operations to the host runtime.
+fn capability_descriptor() -> CapabilityDescriptor {
+    CapabilityDescriptor::builder()
+        .id(CAPABILITY_ID)
+        .name("waSCC Default HTTP Server (Actix Web)")
+        .long_description("An Actix-web implementation of the wascc:http_server capability specification")
+        .version(env!("CARGO_PKG_VERSION"))
+        .revision(REVISION)
+        .with_operation(
+            OP_HANDLE_REQUEST,
+            OperationDirection::ToActor,
+            "Delivers an inbound HTTP request to the bound actor and returns its response",
+        )
+        .build()
+}
+
 async fn request_handler(
     req: HttpRequest,
     payload: Bytes,
     state: web::Data<Arc<RwLock<Box<dyn Dispatcher>>>>,
-    module: web::Data<String>,
+    routes: web::Data<Arc<RwLock<Vec<RouteEntry>>>>,
 ) -> HttpResponse {
+    let module = match resolve_module(&req, &routes.read().unwrap()) {
+        Some(module) => module,
+        None => {
+            return HttpResponse::with_body(
+                StatusCode::from_u16(404u16).unwrap(),
+                Body::from_slice(b"No actor registered for this host/path"),
+            );
+        }
+    };
+
     let request = codec::http::Request {
         method: req.method().as_str().to_string(),
         path: req.uri().path().to_string(),
@@ -189,15 +530,25 @@ async fn request_handler(
 
     let resp = {
         let lock = (*state).read().unwrap();
-        lock.dispatch(module.get_ref(), "HandleRequest", &buf)
+        lock.dispatch(&module, "HandleRequest", &buf)
     };
     match resp {
         Ok(r) => {
             let r = deserialize::<codec::http::Response>(r.as_slice()).unwrap();
-            HttpResponse::with_body(
-                StatusCode::from_u16(r.status_code as _).unwrap(),
-                Body::from_slice(&r.body),
-            )
+            let status = StatusCode::from_u16(r.status_code as _).unwrap();
+            let mut builder = HttpResponse::build(status);
+            for (hname, hval) in r.header.iter() {
+                if is_hop_by_hop_header(hname) {
+                    continue;
+                }
+                if let (Ok(name), Ok(value)) = (
+                    HeaderName::from_bytes(hname.as_bytes()),
+                    HeaderValue::from_str(hval),
+                ) {
+                    builder.header(name, value);
+                }
+            }
+            builder.body(Body::from_slice(&r.body))
         }
         Err(e) => {
             error!("Guest failed to handle HTTP request: {}", e);
@@ -209,6 +560,73 @@ async fn request_handler(
     }
 }
 
+/// Headers that are specific to a single hop and must not be copied verbatim
+/// from a guest-produced response onto the outgoing actix-web response.
+fn is_hop_by_hop_header(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "connection"
+            | "keep-alive"
+            | "proxy-authenticate"
+            | "proxy-authorization"
+            | "te"
+            | "trailer"
+            | "transfer-encoding"
+            | "upgrade"
+            | "content-length"
+    )
+}
+
+/// Strips the port (and, for an IPv6 literal like `[::1]:8080`, the
+/// brackets) from a raw `Host` header value, lower-casing the result for
+/// case-insensitive comparison against `RouteEntry::host`.
+fn host_from_header(raw: &str) -> String {
+    match raw.parse::<actix_web::http::uri::Authority>() {
+        Ok(authority) => authority
+            .host()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_ascii_lowercase(),
+        Err(_) => raw.to_ascii_lowercase(),
+    }
+}
+
+/// Picks the actor that should handle `req` out of a listener's routing
+/// table: an exact virtual-host match wins, then the longest matching
+/// path prefix, then the catch-all route (the common case of one actor
+/// occupying the whole listener).
+fn resolve_module(req: &HttpRequest, routes: &[RouteEntry]) -> Option<String> {
+    let host = req
+        .headers()
+        .get(actix_web::http::header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .map(host_from_header);
+
+    if let Some(host) = &host {
+        if let Some(entry) = routes
+            .iter()
+            .find(|r| r.host.as_deref() == Some(host.as_str()))
+        {
+            return Some(entry.module_id.clone());
+        }
+    }
+
+    let path = req.uri().path();
+    if let Some(entry) = routes
+        .iter()
+        .filter(|r| r.host.is_none() && r.path_prefix.is_some())
+        .filter(|r| path.starts_with(r.path_prefix.as_ref().unwrap().as_str()))
+        .max_by_key(|r| r.path_prefix.as_ref().unwrap().len())
+    {
+        return Some(entry.module_id.clone());
+    }
+
+    routes
+        .iter()
+        .find(|r| r.host.is_none() && r.path_prefix.is_none())
+        .map(|r| r.module_id.clone())
+}
+
 fn extract_headers(req: &HttpRequest) -> HashMap<String, String> {
     let mut hm = HashMap::new();
 
@@ -221,3 +639,75 @@ fn extract_headers(req: &HttpRequest) -> HashMap<String, String> {
 
     hm
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn route(module: &str, host: Option<&str>, path_prefix: Option<&str>) -> RouteEntry {
+        RouteEntry {
+            module_id: module.to_string(),
+            host: host.map(|h| h.to_ascii_lowercase()),
+            path_prefix: path_prefix.map(|p| p.to_string()),
+        }
+    }
+
+    #[test]
+    fn vhost_match_wins_over_path_prefix() {
+        let routes = vec![
+            route("by-host", Some("api.example.com"), None),
+            route("by-path", None, Some("/anything")),
+        ];
+        let req = TestRequest::get()
+            .header("Host", "api.example.com")
+            .uri("/anything")
+            .to_http_request();
+
+        assert_eq!(resolve_module(&req, &routes), Some("by-host".to_string()));
+    }
+
+    #[test]
+    fn longest_path_prefix_wins() {
+        let routes = vec![
+            route("v1", None, Some("/api")),
+            route("v2", None, Some("/api/v2")),
+        ];
+        let req = TestRequest::get().uri("/api/v2/users").to_http_request();
+
+        assert_eq!(resolve_module(&req, &routes), Some("v2".to_string()));
+    }
+
+    #[test]
+    fn ipv6_literal_host_header_is_matched() {
+        let routes = vec![route("ipv6", Some("::1"), None)];
+        let req = TestRequest::get()
+            .header("Host", "[::1]:8080")
+            .uri("/")
+            .to_http_request();
+
+        assert_eq!(resolve_module(&req, &routes), Some("ipv6".to_string()));
+    }
+
+    #[test]
+    fn falls_back_to_catch_all_when_nothing_else_matches() {
+        let routes = vec![
+            route("by-host", Some("other.example.com"), None),
+            route("catch-all", None, None),
+        ];
+        let req = TestRequest::get().uri("/unmatched").to_http_request();
+
+        assert_eq!(
+            resolve_module(&req, &routes),
+            Some("catch-all".to_string())
+        );
+    }
+
+    #[test]
+    fn no_match_returns_none() {
+        let routes = vec![route("only-prefix", None, Some("/api"))];
+        let req = TestRequest::get().uri("/other").to_http_request();
+
+        assert_eq!(resolve_module(&req, &routes), None);
+    }
+}